@@ -0,0 +1,380 @@
+use std::{env, path::PathBuf, time::Duration};
+
+use serde::Deserialize;
+
+/// Settings baked into the binary, used as the bottom layer of the
+/// precedence stack described on [`Config::load`].
+const DEFAULTS_TOML: &str = include_str!("../defaults.toml");
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("failed to read config file {path:?}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("failed to parse config file {path:?}: {source}")]
+    Toml {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+    #[error("bundled defaults.toml failed to parse: {0}")]
+    InvalidDefaults(toml::de::Error),
+    #[error("{0} must be set via config file or environment variable")]
+    MissingRequired(&'static str),
+    #[error("{field} must be set (via config file or environment variable) when store_backend = {backend:?}")]
+    MissingForBackend { field: &'static str, backend: String },
+}
+
+/// Mirrors `defaults.toml` and any user config file field-for-field, with
+/// every field optional so each layer only needs to specify what it
+/// overrides. [`RawConfig::resolve`] turns the fully-merged result into the
+/// concrete [`Config`] the rest of the crate uses, applying defaults and
+/// validating required fields.
+#[derive(Debug, Default, Deserialize)]
+struct RawConfig {
+    store_backend: Option<String>,
+    api_url: Option<String>,
+    api_key: Option<String>,
+    monitored_directory: Option<String>,
+    archive_directory: Option<String>,
+    s3_bucket: Option<String>,
+    s3_prefix: Option<String>,
+    dedup_db_path: Option<String>,
+    dedup_max_age_days: Option<u64>,
+    dedup_cache_size: Option<usize>,
+    retry_db_path: Option<String>,
+    filename_profiles_path: Option<String>,
+    debounce_seconds: Option<u64>,
+    min_duration_seconds: Option<f64>,
+    log_completed_requests: Option<bool>,
+    danger_accept_invalid_certs: Option<bool>,
+    metrics_listen_addr: Option<String>,
+    retry_base_backoff_seconds: Option<u64>,
+    retry_max_backoff_seconds: Option<u64>,
+    retry_max_attempts: Option<u32>,
+}
+
+impl RawConfig {
+    fn from_toml_str(raw: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(raw)
+    }
+
+    fn from_file(path: &std::path::Path) -> Result<Self, ConfigError> {
+        let raw = std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::from_toml_str(&raw).map_err(|source| ConfigError::Toml {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// Reads overrides from environment variables, one per field named after
+    /// the field in upper case. This includes `API_URL`, `API_KEY` and
+    /// `MONITORED_DIRECTORY`, which this crate has always honored, so existing
+    /// deployments keep working unchanged.
+    fn from_env() -> Self {
+        Self {
+            store_backend: env::var("STORE_BACKEND").ok(),
+            api_url: env::var("API_URL").ok(),
+            api_key: env::var("API_KEY").ok(),
+            monitored_directory: env::var("MONITORED_DIRECTORY").ok(),
+            archive_directory: env::var("ARCHIVE_DIRECTORY").ok(),
+            s3_bucket: env::var("S3_BUCKET").ok(),
+            s3_prefix: env::var("S3_PREFIX").ok(),
+            dedup_db_path: env::var("DEDUP_DB_PATH").ok(),
+            dedup_max_age_days: env::var("DEDUP_MAX_AGE_DAYS").ok().and_then(|v| v.parse().ok()),
+            dedup_cache_size: env::var("DEDUP_CACHE_SIZE").ok().and_then(|v| v.parse().ok()),
+            retry_db_path: env::var("RETRY_DB_PATH").ok(),
+            filename_profiles_path: env::var("FILENAME_PROFILES_PATH").ok(),
+            debounce_seconds: env::var("DEBOUNCE_SECONDS").ok().and_then(|v| v.parse().ok()),
+            min_duration_seconds: env::var("MIN_DURATION_SECONDS").ok().and_then(|v| v.parse().ok()),
+            log_completed_requests: env::var("LOG_COMPLETED_REQUESTS").ok().as_deref().map(parse_bool),
+            danger_accept_invalid_certs: env::var("DANGER_ACCEPT_INVALID_CERTS").ok().as_deref().map(parse_bool),
+            metrics_listen_addr: env::var("METRICS_LISTEN_ADDR").ok(),
+            retry_base_backoff_seconds: env::var("RETRY_BASE_BACKOFF_SECONDS").ok().and_then(|v| v.parse().ok()),
+            retry_max_backoff_seconds: env::var("RETRY_MAX_BACKOFF_SECONDS").ok().and_then(|v| v.parse().ok()),
+            retry_max_attempts: env::var("RETRY_MAX_ATTEMPTS").ok().and_then(|v| v.parse().ok()),
+        }
+    }
+
+    /// Layers `over` on top of `self`, field by field, so later layers
+    /// (config file, then env) only need to set what they actually override.
+    fn merge(self, over: Self) -> Self {
+        Self {
+            store_backend: over.store_backend.or(self.store_backend),
+            api_url: over.api_url.or(self.api_url),
+            api_key: over.api_key.or(self.api_key),
+            monitored_directory: over.monitored_directory.or(self.monitored_directory),
+            archive_directory: over.archive_directory.or(self.archive_directory),
+            s3_bucket: over.s3_bucket.or(self.s3_bucket),
+            s3_prefix: over.s3_prefix.or(self.s3_prefix),
+            dedup_db_path: over.dedup_db_path.or(self.dedup_db_path),
+            dedup_max_age_days: over.dedup_max_age_days.or(self.dedup_max_age_days),
+            dedup_cache_size: over.dedup_cache_size.or(self.dedup_cache_size),
+            retry_db_path: over.retry_db_path.or(self.retry_db_path),
+            filename_profiles_path: over.filename_profiles_path.or(self.filename_profiles_path),
+            debounce_seconds: over.debounce_seconds.or(self.debounce_seconds),
+            min_duration_seconds: over.min_duration_seconds.or(self.min_duration_seconds),
+            log_completed_requests: over.log_completed_requests.or(self.log_completed_requests),
+            danger_accept_invalid_certs: over.danger_accept_invalid_certs.or(self.danger_accept_invalid_certs),
+            metrics_listen_addr: over.metrics_listen_addr.or(self.metrics_listen_addr),
+            retry_base_backoff_seconds: over.retry_base_backoff_seconds.or(self.retry_base_backoff_seconds),
+            retry_max_backoff_seconds: over.retry_max_backoff_seconds.or(self.retry_max_backoff_seconds),
+            retry_max_attempts: over.retry_max_attempts.or(self.retry_max_attempts),
+        }
+    }
+
+    /// Validates required fields and fills in any field still unset with a
+    /// built-in fallback (the same values `defaults.toml` ships), producing
+    /// the concrete settings the rest of the crate works with.
+    fn resolve(self) -> Result<Config, ConfigError> {
+        let store_backend = self.store_backend.unwrap_or_else(|| "http".to_string());
+
+        let monitored_directory = self
+            .monitored_directory
+            .map(PathBuf::from)
+            .ok_or(ConfigError::MissingRequired("monitored_directory"))?;
+
+        let mut api_url = None;
+        let mut api_key = None;
+        let mut archive_directory = None;
+        let mut s3_bucket = None;
+        match store_backend.as_str() {
+            "filesystem" => {
+                archive_directory = Some(self.archive_directory.map(PathBuf::from).ok_or(
+                    ConfigError::MissingForBackend { field: "archive_directory", backend: store_backend.clone() },
+                )?);
+            }
+            "s3" => {
+                s3_bucket = Some(self.s3_bucket.ok_or(ConfigError::MissingForBackend {
+                    field: "s3_bucket",
+                    backend: store_backend.clone(),
+                })?);
+            }
+            // Anything else (including "http") is treated as the HTTP backend;
+            // an unrecognized value falls back to it with a warning in `build_store`.
+            _ => {
+                api_url = Some(self.api_url.ok_or(ConfigError::MissingForBackend {
+                    field: "api_url",
+                    backend: store_backend.clone(),
+                })?);
+                api_key = Some(self.api_key.ok_or(ConfigError::MissingForBackend {
+                    field: "api_key",
+                    backend: store_backend.clone(),
+                })?);
+            }
+        }
+
+        Ok(Config {
+            store_backend,
+            api_url,
+            api_key,
+            monitored_directory,
+            archive_directory,
+            s3_bucket,
+            s3_prefix: self.s3_prefix.unwrap_or_else(|| "transcriptions".to_string()),
+            dedup_db_path: self.dedup_db_path.map(PathBuf::from).unwrap_or_else(|| PathBuf::from("dedup.sled")),
+            dedup_max_age_days: self.dedup_max_age_days,
+            dedup_cache_size: self.dedup_cache_size.unwrap_or(256),
+            retry_db_path: self.retry_db_path.map(PathBuf::from).unwrap_or_else(|| PathBuf::from("retry.sled")),
+            filename_profiles_path: self.filename_profiles_path.map(PathBuf::from),
+            debounce: Duration::from_secs(self.debounce_seconds.unwrap_or(3)),
+            min_duration_seconds: self.min_duration_seconds.unwrap_or(0.0),
+            log_completed_requests: self.log_completed_requests.unwrap_or(true),
+            danger_accept_invalid_certs: self.danger_accept_invalid_certs.unwrap_or(true),
+            metrics_listen_addr: self.metrics_listen_addr.unwrap_or_else(|| "0.0.0.0:9100".to_string()),
+            retry_base_backoff: Duration::from_secs(self.retry_base_backoff_seconds.unwrap_or(2)),
+            retry_max_backoff: Duration::from_secs(self.retry_max_backoff_seconds.unwrap_or(5 * 60)),
+            retry_max_attempts: self.retry_max_attempts.unwrap_or(10),
+        })
+    }
+}
+
+fn parse_bool(s: &str) -> bool {
+    s != "0" && s.to_lowercase() != "false"
+}
+
+/// Resolved settings for the watcher, assembled by [`Config::load`] from
+/// `defaults.toml`, an optional user config file, and environment variables.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub store_backend: String,
+    pub api_url: Option<String>,
+    pub api_key: Option<String>,
+    pub monitored_directory: PathBuf,
+    pub archive_directory: Option<PathBuf>,
+    pub s3_bucket: Option<String>,
+    pub s3_prefix: String,
+    pub dedup_db_path: PathBuf,
+    pub dedup_max_age_days: Option<u64>,
+    pub dedup_cache_size: usize,
+    pub retry_db_path: PathBuf,
+    pub filename_profiles_path: Option<PathBuf>,
+    pub debounce: Duration,
+    pub min_duration_seconds: f64,
+    pub log_completed_requests: bool,
+    pub danger_accept_invalid_certs: bool,
+    pub metrics_listen_addr: String,
+    pub retry_base_backoff: Duration,
+    pub retry_max_backoff: Duration,
+    pub retry_max_attempts: u32,
+}
+
+impl Config {
+    /// Loads settings with the following precedence, lowest to highest:
+    ///
+    /// 1. The `defaults.toml` bundled into the binary at compile time.
+    /// 2. A user config file: the path given by `--config <path>` (or
+    ///    `--config=<path>`), or else `$XDG_CONFIG_HOME/transcription_uploader/config.toml`
+    ///    (via the `dirs` crate) if it exists. Neither is required.
+    /// 3. Environment variables (and `.env`, since `main` calls `dotenv()`
+    ///    before this runs), one per field, named after the field in upper
+    ///    case (`API_URL`, `MONITORED_DIRECTORY`, ...).
+    ///
+    /// This mirrors the layering pict-rs uses for its own TOML config: later
+    /// layers override earlier ones field-by-field rather than replacing the
+    /// whole layer, so a config file only needs to mention what it changes.
+    pub fn load() -> Result<Self, ConfigError> {
+        let defaults = RawConfig::from_toml_str(DEFAULTS_TOML).map_err(ConfigError::InvalidDefaults)?;
+
+        let file = match explicit_config_path() {
+            // `--config` named a path explicitly; a missing file there is an error
+            // rather than silently falling back to defaults.
+            Some(path) => RawConfig::from_file(&path)?,
+            None => match default_config_path() {
+                Some(path) if path.exists() => RawConfig::from_file(&path)?,
+                _ => RawConfig::default(),
+            },
+        };
+
+        let env = RawConfig::from_env();
+        defaults.merge(file).merge(env).resolve()
+    }
+}
+
+/// The path passed via `--config <path>` or `--config=<path>`, if any.
+fn explicit_config_path() -> Option<PathBuf> {
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(PathBuf::from(path));
+        }
+        if arg == "--config" {
+            return args.next().map(PathBuf::from);
+        }
+    }
+    None
+}
+
+/// The `dirs`-based default config path, used when `--config` isn't given.
+/// Unlike an explicit `--config`, it's fine for this one not to exist.
+fn default_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join("transcription_uploader").join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> RawConfig {
+        RawConfig {
+            monitored_directory: Some("/recordings".to_string()),
+            ..RawConfig::default()
+        }
+    }
+
+    #[test]
+    fn merge_prefers_the_higher_layer_field_by_field() {
+        let defaults = RawConfig {
+            store_backend: Some("http".to_string()),
+            dedup_cache_size: Some(256),
+            ..RawConfig::default()
+        };
+        let file = RawConfig {
+            dedup_cache_size: Some(512),
+            ..RawConfig::default()
+        };
+        let env = RawConfig {
+            store_backend: Some("filesystem".to_string()),
+            ..RawConfig::default()
+        };
+
+        let merged = defaults.merge(file).merge(env);
+
+        // env overrides store_backend, file overrides dedup_cache_size (env
+        // didn't set it), and nothing touches fields only defaults set.
+        assert_eq!(merged.store_backend, Some("filesystem".to_string()));
+        assert_eq!(merged.dedup_cache_size, Some(512));
+    }
+
+    #[test]
+    fn resolve_requires_monitored_directory() {
+        let err = RawConfig::default().resolve().unwrap_err();
+        assert!(matches!(err, ConfigError::MissingRequired("monitored_directory")));
+    }
+
+    #[test]
+    fn resolve_requires_api_url_and_api_key_for_http_backend() {
+        let err = base().resolve().unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::MissingForBackend { field: "api_url", .. }
+        ));
+
+        let with_url = RawConfig {
+            api_url: Some("https://example.com/upload".to_string()),
+            ..base()
+        };
+        let err = with_url.resolve().unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::MissingForBackend { field: "api_key", .. }
+        ));
+    }
+
+    #[test]
+    fn resolve_requires_archive_directory_for_filesystem_backend() {
+        let config = RawConfig {
+            store_backend: Some("filesystem".to_string()),
+            ..base()
+        };
+        let err = config.resolve().unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::MissingForBackend { field: "archive_directory", .. }
+        ));
+    }
+
+    #[test]
+    fn resolve_requires_s3_bucket_for_s3_backend() {
+        let config = RawConfig {
+            store_backend: Some("s3".to_string()),
+            ..base()
+        };
+        let err = config.resolve().unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::MissingForBackend { field: "s3_bucket", .. }
+        ));
+    }
+
+    #[test]
+    fn resolve_fills_in_defaults_for_unset_fields() {
+        let config = RawConfig {
+            api_url: Some("https://example.com/upload".to_string()),
+            api_key: Some("secret".to_string()),
+            ..base()
+        }
+        .resolve()
+        .unwrap();
+
+        assert_eq!(config.dedup_cache_size, 256);
+        assert_eq!(config.debounce, Duration::from_secs(3));
+        assert_eq!(config.retry_max_attempts, 10);
+    }
+}
@@ -0,0 +1,174 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use lru::LruCache;
+use tracing::error;
+
+use crate::ProcessedFile;
+
+impl ProcessedFile {
+    /// Encodes `(stem, size, modified)` as a sled key. `modified` is folded in
+    /// as seconds-since-epoch so the key is stable across processes.
+    fn db_key(&self) -> Vec<u8> {
+        let modified_secs = self
+            .modified
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO)
+            .as_secs();
+        format!("{}\0{}\0{}", self.stem, self.size, modified_secs).into_bytes()
+    }
+}
+
+/// Durable dedup store keyed on the same `(stem, size, modified)` signature
+/// `ProcessedFile` already computes. Backed by a sled tree so that a restart
+/// (crash, redeploy) doesn't forget every file the watcher has already sent,
+/// with a small in-memory LRU cache in front so re-checking a just-seen file
+/// doesn't pay for a disk read.
+pub struct DedupStore {
+    db: sled::Db,
+    cache: Mutex<LruCache<Vec<u8>, ()>>,
+}
+
+impl DedupStore {
+    /// Opens (or creates) the sled database at `path`, keeping up to
+    /// `cache_size` recently-seen signatures hot in memory in front of it so
+    /// the common case (the same file firing a couple of debounced events in
+    /// a row) doesn't round-trip to disk.
+    pub fn open(path: &std::path::Path, cache_size: usize) -> sled::Result<Self> {
+        let db = sled::open(path)?;
+        Ok(Self {
+            db,
+            cache: Mutex::new(LruCache::new(
+                std::num::NonZeroUsize::new(cache_size.max(1)).unwrap(),
+            )),
+        })
+    }
+
+    pub fn has_already_been_processed(&self, signature: &ProcessedFile) -> bool {
+        let key = signature.db_key();
+
+        if self.cache.lock().unwrap().contains(&key) {
+            return true;
+        }
+
+        match self.db.contains_key(&key) {
+            Ok(found) => {
+                if found {
+                    self.cache.lock().unwrap().put(key, ());
+                }
+                found
+            }
+            Err(e) => {
+                error!("Dedup store lookup failed, assuming not processed: {}", e);
+                false
+            }
+        }
+    }
+
+    pub fn mark_as_processed(&self, signature: ProcessedFile) {
+        let key = signature.db_key();
+        let processed_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO)
+            .as_secs();
+
+        if let Err(e) = self.db.insert(&key, &processed_at.to_be_bytes()) {
+            error!("Failed to persist dedup entry for {}: {}", signature.stem, e);
+            return;
+        }
+        let _ = self.db.flush();
+
+        self.cache.lock().unwrap().put(key, ());
+    }
+
+    /// Removes entries marked as processed longer than `max_age` ago, so the
+    /// database doesn't grow forever on a watcher that's been up for years.
+    /// Returns how many entries were removed.
+    pub fn prune_older_than(&self, max_age: Duration) -> sled::Result<usize> {
+        let cutoff = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO)
+            .saturating_sub(max_age)
+            .as_secs();
+
+        let mut removed = 0;
+        for entry in self.db.iter() {
+            let (key, value) = entry?;
+            let processed_at = value
+                .as_ref()
+                .try_into()
+                .map(u64::from_be_bytes)
+                .unwrap_or(u64::MAX);
+            if processed_at < cutoff {
+                self.db.remove(&key)?;
+                removed += 1;
+            }
+        }
+        if removed > 0 {
+            self.db.flush()?;
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signature(stem: &str, size: u64, modified_secs: u64) -> ProcessedFile {
+        ProcessedFile {
+            stem: stem.to_string(),
+            size,
+            modified: UNIX_EPOCH + Duration::from_secs(modified_secs),
+        }
+    }
+
+    #[test]
+    fn db_key_encodes_stem_size_and_modified_secs() {
+        let key = signature("20241223_204051_call", 1234, 1_703_361_651).db_key();
+        assert_eq!(
+            key,
+            format!("20241223_204051_call\0{}\0{}", 1234, 1_703_361_651).into_bytes()
+        );
+    }
+
+    #[test]
+    fn has_already_been_processed_is_false_until_marked() {
+        let dir = tempfile::tempdir().unwrap();
+        let dedup = DedupStore::open(&dir.path().join("dedup.sled"), 16).unwrap();
+        let sig = signature("call", 100, 1_700_000_000);
+
+        assert!(!dedup.has_already_been_processed(&sig));
+        dedup.mark_as_processed(sig.clone());
+        assert!(dedup.has_already_been_processed(&sig));
+    }
+
+    #[test]
+    fn prune_older_than_removes_only_stale_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let dedup = DedupStore::open(&dir.path().join("dedup.sled"), 16).unwrap();
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let stale = signature("stale", 1, now - 10 * 86_400);
+        let fresh = signature("fresh", 1, now);
+
+        // `mark_as_processed` always stamps "now", so insert the stale entry's
+        // processed-at timestamp directly to simulate one from 10 days ago.
+        dedup
+            .db
+            .insert(stale.db_key(), &(now - 10 * 86_400).to_be_bytes())
+            .unwrap();
+        dedup.mark_as_processed(fresh.clone());
+
+        let removed = dedup.prune_older_than(Duration::from_secs(5 * 86_400)).unwrap();
+
+        assert_eq!(removed, 1);
+        assert!(!dedup.has_already_been_processed(&stale));
+        assert!(dedup.has_already_been_processed(&fresh));
+    }
+}
@@ -0,0 +1,122 @@
+use std::path::Path;
+
+use regex::Regex;
+use serde::Deserialize;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProfileError {
+    #[error("failed to read profiles file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("failed to parse profiles file: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("profile {name:?} has an invalid regex: {source}")]
+    InvalidRegex {
+        name: String,
+        #[source]
+        source: regex::Error,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+struct ProfilesFile {
+    profile: Vec<ProfileDef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProfileDef {
+    name: String,
+    /// Must define named capture groups: `timestamp`, `talkgroup`, and
+    /// optionally `radio`, `system`, `site`.
+    pattern: String,
+}
+
+struct CompiledProfile {
+    name: String,
+    regex: Regex,
+}
+
+/// The fields a filename-parsing profile can recognize. `radio`, `system`, and
+/// `site` are genuinely optional — a profile that doesn't capture them (or a
+/// filename that doesn't populate them) leaves the field `None` rather than
+/// inventing a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedFilename {
+    pub profile_name: String,
+    pub timestamp: String,
+    pub talkgroup: String,
+    pub radio: Option<String>,
+    pub system: Option<String>,
+    pub site: Option<String>,
+}
+
+/// An ordered set of named filename-parsing profiles, tried in order until
+/// one matches. This replaces baking a single SDRTrunk naming convention
+/// into the binary, so other SDRTrunk/Trunk-Recorder filename formats can be
+/// supported by editing config instead of recompiling.
+pub struct FilenameProfiles {
+    profiles: Vec<CompiledProfile>,
+}
+
+impl FilenameProfiles {
+    /// Loads profiles from a TOML file shaped like:
+    ///
+    /// ```toml
+    /// [[profile]]
+    /// name = "sdrtrunk-to-from"
+    /// pattern = '''(?P<timestamp>\d{8}_\d{6}).*__TO_[A-Za-z]?(?P<talkgroup>\d+)(?:_?\[[^\]]*\])?(?:_FROM_(?P<radio>\d+))?'''
+    /// ```
+    pub fn load(path: &Path) -> Result<Self, ProfileError> {
+        let raw = std::fs::read_to_string(path)?;
+        let file: ProfilesFile = toml::from_str(&raw)?;
+        Self::compile(file.profile.into_iter().map(|p| (p.name, p.pattern)))
+    }
+
+    /// The SDRTrunk `__TO_<talkgroup>[_FROM_<radio>]` convention this crate
+    /// has always supported, used when no profiles file is configured.
+    pub fn builtin_defaults() -> Self {
+        Self::compile([(
+            "sdrtrunk-to-from".to_string(),
+            r"(?P<timestamp>\d{8}_\d{6}).*__TO_[A-Za-z]?(?P<talkgroup>\d+)(?:_?\[[^\]]*\])?(?:_FROM_(?P<radio>\d+))?"
+                .to_string(),
+        )])
+        .expect("builtin default profile must compile")
+    }
+
+    fn compile(defs: impl IntoIterator<Item = (String, String)>) -> Result<Self, ProfileError> {
+        let profiles = defs
+            .into_iter()
+            .map(|(name, pattern)| {
+                Regex::new(&pattern)
+                    .map(|regex| CompiledProfile { name: name.clone(), regex })
+                    .map_err(|source| ProfileError::InvalidRegex { name, source })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { profiles })
+    }
+
+    /// Tries each profile in order and returns the first match, along with
+    /// which profile matched.
+    pub fn parse(&self, filename: &str) -> Option<ParsedFilename> {
+        for profile in &self.profiles {
+            let Some(captures) = profile.regex.captures(filename) else {
+                continue;
+            };
+            let Some(timestamp) = captures.name("timestamp") else {
+                continue;
+            };
+            let Some(talkgroup) = captures.name("talkgroup") else {
+                continue;
+            };
+
+            return Some(ParsedFilename {
+                profile_name: profile.name.clone(),
+                timestamp: timestamp.as_str().to_string(),
+                talkgroup: talkgroup.as_str().to_string(),
+                radio: captures.name("radio").map(|m| m.as_str().to_string()),
+                system: captures.name("system").map(|m| m.as_str().to_string()),
+                site: captures.name("site").map(|m| m.as_str().to_string()),
+            });
+        }
+        None
+    }
+}
@@ -1,34 +1,33 @@
+mod config;
+mod dedup;
+mod filename_profiles;
+mod metrics;
+mod probe;
+mod retry;
+mod store;
+#[cfg(test)]
+mod tests;
+
 use dotenv::dotenv;
-use notify::{
-    recommended_watcher, EventKind, RecursiveMode, Result as NotifyResult, Watcher,
-};
+use notify::{EventKind, RecursiveMode, Result as NotifyResult, Watcher};
+use notify_debouncer_full::{new_debouncer, DebounceEventResult};
 use once_cell::sync::Lazy;
-use regex::Regex;
-use reqwest::{
-    multipart::{Form, Part},
-    Client,
-};
+use reqwest::Client;
 use std::{
-    collections::{HashMap, HashSet, VecDeque},
-    env,
+    collections::HashSet,
     fs,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{Arc, Mutex},
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
-use tokio::{runtime::Runtime, sync::mpsc};
-use tokio::time::sleep;
+use tokio::runtime::Runtime;
+use tracing::{debug, error, info, warn};
 
-/// Lazily initialized, shared `reqwest::Client` wrapped in an `Arc`.
-/// Avoids `static mut` usage and the associated warnings.
-static CLIENT: Lazy<Arc<Client>> = Lazy::new(|| {
-    Arc::new(
-        Client::builder()
-            .danger_accept_invalid_certs(true)
-            .build()
-            .expect("Failed to create HTTP client"),
-    )
-});
+use config::Config;
+use dedup::DedupStore;
+use filename_profiles::{FilenameProfiles, ParsedFilename};
+use retry::{RetryPolicy, RetryQueue};
+use store::{CallMeta, FilesystemStore, HttpStore, S3Store, Store, StoreOutcome};
 
 /// A small struct holding data that identifies a "processed" transcription file.
 /// We'll use the `.txt` file's path stem, size, and last-modified time.
@@ -39,12 +38,6 @@ struct ProcessedFile {
     modified: SystemTime,
 }
 
-/// A global queue (up to 25 entries) of recently processed files to avoid re-uploads.
-/// We store `(stem, size, modified)` and skip if the exact same file shows up again.
-static PROCESSED_FILES: Lazy<Mutex<VecDeque<ProcessedFile>>> = Lazy::new(|| {
-    Mutex::new(VecDeque::new()) // Start empty
-});
-
 /// NEW: A global set to track files for which an upload is currently in progress.
 /// This helps avoid a race condition where multiple events fire before we get a
 /// chance to mark the file as processed.
@@ -52,129 +45,196 @@ static UPLOADS_IN_PROGRESS: Lazy<Mutex<HashSet<ProcessedFile>>> = Lazy::new(|| {
     Mutex::new(HashSet::new())
 });
 
-/// Lazily initialized, environment-based API URL.
-/// Reads the `API_URL` environment variable (from .env if present).
-static API_URL: Lazy<String> = Lazy::new(|| {
-    env::var("API_URL").expect("API_URL environment variable not set")
-});
+/// Builds the shared `reqwest::Client`, honoring `danger_accept_invalid_certs`
+/// from config instead of always trusting self-signed certs.
+fn build_client(config: &Config) -> Arc<Client> {
+    Arc::new(
+        Client::builder()
+            .danger_accept_invalid_certs(config.danger_accept_invalid_certs)
+            .build()
+            .expect("Failed to create HTTP client"),
+    )
+}
 
-/// Lazily initialized, environment-based API Key.
-/// Reads the `API_KEY` environment variable (from .env if present).
-static API_KEY: Lazy<String> = Lazy::new(|| {
-    env::var("API_KEY").expect("API_KEY environment variable not set")
-});
+/// Builds the upload backend selected by `config.store_backend` (`http`
+/// [default], `filesystem`, or `s3`). `process_and_upload` only ever sees
+/// this as a `dyn Store`, so the dedup/in-progress logic stays identical no
+/// matter where recordings end up.
+async fn build_store(config: &Config, client: Arc<Client>) -> Arc<dyn Store> {
+    match config.store_backend.as_str() {
+        "filesystem" => {
+            let archive_root = config
+                .archive_directory
+                .clone()
+                .expect("archive_directory must be set when store_backend = \"filesystem\"");
+            Arc::new(FilesystemStore::new(archive_root))
+        }
+        "s3" => {
+            let bucket = config
+                .s3_bucket
+                .clone()
+                .expect("s3_bucket must be set when store_backend = \"s3\"");
+            let s3 = object_store::aws::AmazonS3Builder::from_env()
+                .with_bucket_name(bucket)
+                .build()
+                .expect("Failed to configure S3 store");
+            Arc::new(S3Store::new(s3, config.s3_prefix.clone()))
+        }
+        other => {
+            if other != "http" {
+                warn!("Unknown store_backend {:?}, falling back to http", other);
+            }
+            let api_url = config.api_url.clone().expect("api_url must be set for the http store backend");
+            let api_key = config.api_key.clone().expect("api_key must be set for the http store backend");
+            Arc::new(HttpStore::new((*client).clone(), api_url, api_key))
+        }
+    }
+}
+
+/// Opens the durable dedup store at `config.dedup_db_path`, and prunes
+/// entries older than `config.dedup_max_age_days` if that's set.
+fn build_dedup_store(config: &Config) -> Arc<DedupStore> {
+    let dedup = DedupStore::open(&config.dedup_db_path, config.dedup_cache_size)
+        .expect("Failed to open dedup database");
+
+    if let Some(days) = config.dedup_max_age_days {
+        match dedup.prune_older_than(Duration::from_secs(days * 86_400)) {
+            Ok(removed) if removed > 0 => {
+                info!("Pruned {} stale dedup entries older than {} days", removed, days)
+            }
+            Ok(_) => {}
+            Err(e) => error!("Failed to prune dedup database: {}", e),
+        }
+    }
+
+    Arc::new(dedup)
+}
+
+/// Opens the durable retry queue at `config.retry_db_path` with the backoff
+/// policy from config.
+fn build_retry_queue(config: &Config) -> Arc<RetryQueue> {
+    Arc::new(
+        RetryQueue::open(&config.retry_db_path, RetryPolicy::from(config))
+            .expect("Failed to open retry queue"),
+    )
+}
+
+/// Loads filename-parsing profiles from `config.filename_profiles_path` if
+/// set, falling back to the built-in SDRTrunk `__TO_/_FROM_` profile otherwise.
+fn build_filename_profiles(config: &Config) -> Arc<FilenameProfiles> {
+    match &config.filename_profiles_path {
+        Some(path) => Arc::new(
+            FilenameProfiles::load(path).expect("Failed to load filename_profiles_path"),
+        ),
+        None => Arc::new(FilenameProfiles::builtin_defaults()),
+    }
+}
 
 fn main() -> NotifyResult<()> {
     dotenv().ok();
-    let monitored_directory = env::var("MONITORED_DIRECTORY")
-        .expect("MONITORED_DIRECTORY environment variable not set");
-    let root_path_buf = PathBuf::from(&monitored_directory);
-    println!("Monitoring directory: {:?}", root_path_buf);
+
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .init();
+
+    let config = Arc::new(Config::load().expect("Failed to load configuration"));
+    metrics::install(&config.metrics_listen_addr);
+
+    let root_path_buf = config.monitored_directory.clone();
+    info!("Monitoring directory: {:?}", root_path_buf);
 
     let rt = Runtime::new().unwrap();
     rt.block_on(async {
-        // 1) Set up the raw (std::sync::mpsc) channel for the notify watcher.
-        let (raw_tx, raw_rx) = std::sync::mpsc::channel();
-        let mut watcher = recommended_watcher(move |res| raw_tx.send(res).unwrap())?;
-        watcher.watch(&root_path_buf, RecursiveMode::Recursive)?;
-
-        // 2) Set up an async MPSC channel for “debouncing” file events.
-        let (debounce_tx, mut debounce_rx) = mpsc::unbounded_channel::<PathBuf>();
-
-        // 3) Spawn a background task that coalesces events and waits for files to stabilize.
-        let debounce_task = tokio::spawn(async move {
-            let mut last_update: HashMap<PathBuf, Instant> = HashMap::new();
-            let mut in_flight: HashSet<PathBuf> = HashSet::new();
-
-            while let Some(path) = debounce_rx.recv().await {
-                let now = Instant::now();
-                last_update.insert(path.clone(), now);
-
-                // If we haven't already spawned a "wait and check" task for this path,
-                // mark it as in-flight and spawn one.
-                if !in_flight.contains(&path) {
-                    in_flight.insert(path.clone());
-
-                    let path_clone = path.clone();
-                    let last_update_clone = last_update.clone();
-                    let root_path_clone = root_path_buf.clone();
-
-                    tokio::spawn(async move {
-                        // Debounce interval
-                        let debounce_delay = Duration::from_secs(3);
-
-                        loop {
-                            sleep(debounce_delay).await;
-                            // Check if the file has changed since we started waiting.
-                            if let Some(last) = last_update_clone.get(&path_clone).cloned() {
-                                let elapsed = Instant::now().duration_since(last);
-                                if elapsed >= debounce_delay {
-                                    // The file has been stable for 3s => let's process it.
-                                    break;
-                                }
-                            } else {
-                                // If it's no longer in the map, it might have been removed or renamed.
-                                return;
-                            }
+        let client = build_client(&config);
+        let store = build_store(&config, client).await;
+        let dedup = build_dedup_store(&config);
+        let retry_queue = build_retry_queue(&config);
+        let _retry_worker = Arc::clone(&retry_queue).spawn_worker(Arc::clone(&store), Arc::clone(&dedup));
+        let filename_profiles = build_filename_profiles(&config);
+
+        // 1) Set up the debounced watcher. `notify-debouncer-full` coalesces Create/Modify/
+        // Rename events per file ID (via `FileIdMap`) over `config.debounce`, so an atomic
+        // rename-into-place (the common pattern for recorders that write a temp file then
+        // rename it) is tracked as the same file rather than racing a fresh "create".
+        let (raw_tx, raw_rx) = std::sync::mpsc::channel::<DebounceEventResult>();
+        let mut debouncer = new_debouncer(config.debounce, None, move |result| {
+            let _ = raw_tx.send(result);
+        })
+        .map_err(|e| notify::Error::generic(&e.to_string()))?;
+        debouncer
+            .watcher()
+            .watch(&root_path_buf, RecursiveMode::Recursive)
+            .map_err(|e| notify::Error::generic(&e.to_string()))?;
+
+        // 2) Read batches of already-debounced events and dispatch each stabilized path.
+        while let Ok(result) = raw_rx.recv() {
+            match result {
+                Ok(events) => {
+                    for event in events {
+                        if !matches!(
+                            event.kind,
+                            EventKind::Create(_) | EventKind::Modify(_)
+                        ) {
+                            continue;
                         }
-
-                        // Process (upload) only if it meets our "should_process_file" logic.
-                        if should_process_file(&path_clone, &root_path_clone) {
-                            process_and_upload(&path_clone).await;
+                        for path in &event.paths {
+                            if should_process_file(path, &root_path_buf) {
+                                let path_clone = path.clone();
+                                let store_clone = Arc::clone(&store);
+                                let dedup_clone = Arc::clone(&dedup);
+                                let retry_queue_clone = Arc::clone(&retry_queue);
+                                let filename_profiles_clone = Arc::clone(&filename_profiles);
+                                let config_clone = Arc::clone(&config);
+                                tokio::spawn(async move {
+                                    process_and_upload(
+                                        &path_clone,
+                                        &store_clone,
+                                        &dedup_clone,
+                                        &retry_queue_clone,
+                                        &filename_profiles_clone,
+                                        &config_clone,
+                                    )
+                                    .await;
+                                });
+                            }
                         }
-                    });
+                    }
                 }
-            }
-
-            // Return a result for demonstration. We'll convert to notify::Error if needed.
-            Ok::<(), std::io::Error>(())
-        });
-
-        // 4) Read events from the watcher synchronously. Forward relevant ones to the debounce channel.
-        while let Ok(event_res) = raw_rx.recv() {
-            match event_res {
-                Ok(event) => {
-                    // We only care about Create/Modify for potential new or updated files
-                    if let EventKind::Create(_) | EventKind::Modify(_) = event.kind {
-                        for path in event.paths {
-                            let _ = debounce_tx.send(path);
-                        }
+                Err(errors) => {
+                    for e in errors {
+                        error!("Error handling event: {:?}", e);
                     }
                 }
-                Err(e) => eprintln!("Error handling event: {:?}", e),
-            }
-        }
-
-        // 5) Handle the result of the debounce task explicitly.
-        match debounce_task.await {
-            Ok(Ok(())) => println!("Debounce task finished successfully."),
-            Ok(Err(e)) => {
-                // The async block inside `tokio::spawn` returned an error. Convert std::io::Error -> notify::Error if desired.
-                return Err(notify::Error::from(e));
-            }
-            Err(join_error) => {
-                eprintln!("Debounce task panicked or was cancelled: {}", join_error);
-                return Ok(());
             }
         }
 
-        Ok(())
+        Ok::<(), notify::Error>(())
     })?;
 
     Ok(())
 }
 
 /// Decides whether the file is of interest (non-empty file, not at the root dir, etc.).
-fn should_process_file(file_path: &PathBuf, root_path: &PathBuf) -> bool {
+fn should_process_file(file_path: &Path, root_path: &Path) -> bool {
     let should_process = file_path.parent() != Some(root_path) && file_path.is_file();
-    println!("Should process {:?}: {}", file_path, should_process);
+    debug!("Should process {:?}: {}", file_path, should_process);
     should_process
 }
 
-/// Reads the .mp3 and .txt pair, checks if .txt has content, parses metadata, uploads via `CLIENT`,
-/// but first checks if we've recently processed (or are currently uploading) an identical file.
-async fn process_and_upload(path: &PathBuf) {
-    println!("Stable file -> attempting to upload: {:?}", path);
+/// Reads the .mp3 and .txt pair, checks if .txt has content, parses metadata, and hands the
+/// pair to `store`, but first checks if we've recently processed (or are currently uploading)
+/// an identical file. `store` is whichever backend was selected by `build_store`, so this
+/// function never has to know whether it's talking to the web API, a local archive, or S3.
+async fn process_and_upload(
+    path: &PathBuf,
+    store: &Arc<dyn Store>,
+    dedup: &Arc<DedupStore>,
+    retry_queue: &Arc<RetryQueue>,
+    filename_profiles: &Arc<FilenameProfiles>,
+    config: &Config,
+) {
+    debug!("Stable file -> attempting to upload: {:?}", path);
+    ::metrics::counter!(metrics::FILES_PROCESSED).increment(1);
 
     // Attempt to find matching .mp3 and .txt
     if let Some((mp3_path, txt_path)) = extract_file_info(path) {
@@ -182,13 +242,13 @@ async fn process_and_upload(path: &PathBuf) {
         let txt_metadata = match fs::metadata(&txt_path) {
             Ok(m) => m,
             Err(e) => {
-                eprintln!("Failed to get metadata for .txt: {}", e);
+                error!("Failed to get metadata for .txt: {}", e);
                 return;
             }
         };
         let txt_size = txt_metadata.len();
         if txt_size == 0 {
-            println!("Transcription file is empty, skipping upload.");
+            debug!("Transcription file is empty, skipping upload.");
             return;
         }
         let txt_modified = txt_metadata.modified().unwrap_or(UNIX_EPOCH);
@@ -197,7 +257,7 @@ async fn process_and_upload(path: &PathBuf) {
         let stem = match txt_path.file_stem() {
             Some(s) => s.to_string_lossy().to_string(),
             None => {
-                eprintln!("Could not get file stem for {:?}", txt_path);
+                error!("Could not get file stem for {:?}", txt_path);
                 return;
             }
         };
@@ -210,8 +270,9 @@ async fn process_and_upload(path: &PathBuf) {
         };
 
         // Check if we've already uploaded this exact file in the past
-        if has_already_been_processed(&signature) {
-            println!("Already uploaded this exact transcription, skipping: {}", signature.stem);
+        if dedup.has_already_been_processed(&signature) {
+            ::metrics::counter!(metrics::UPLOADS_SKIPPED_DUPLICATE).increment(1);
+            debug!("Already uploaded this exact transcription, skipping: {}", signature.stem);
             return;
         }
 
@@ -220,7 +281,7 @@ async fn process_and_upload(path: &PathBuf) {
         {
             let mut in_progress = UPLOADS_IN_PROGRESS.lock().unwrap();
             if in_progress.contains(&signature) {
-                println!(
+                debug!(
                     "Upload is already in progress for '{}', skipping duplicate in-flight upload.",
                     signature.stem
                 );
@@ -228,83 +289,95 @@ async fn process_and_upload(path: &PathBuf) {
             }
             // If not in progress, mark it so that any other near-simultaneous event will skip
             in_progress.insert(signature.clone());
+            ::metrics::gauge!(metrics::UPLOADS_IN_PROGRESS).set(in_progress.len() as f64);
         }
 
-        // 1) Read the .txt
-        let txt_bytes = match fs::read(&txt_path) {
-            Ok(bytes) => bytes,
-            Err(e) => {
-                eprintln!("Failed reading .txt file: {}", e);
-                // IMPORTANT: If reading the file fails, we remove the "in progress" entry
-                clear_in_progress(&signature);
-                return;
-            }
-        };
-
-        // 2) Parse the MP3
+        // 1) Parse the MP3 filename for the metadata the store needs.
         let filename = match mp3_path.file_name().and_then(|s| s.to_str()) {
             Some(s) => s,
             None => {
-                println!("Invalid mp3 filename, skipping upload.");
+                warn!("Invalid mp3 filename, skipping upload.");
                 clear_in_progress(&signature);
                 return;
             }
         };
 
-        if let Some((timestamp, talkgroup_id, radio_id)) = parse_filename(filename) {
-            let mp3_bytes = match fs::read(&mp3_path) {
-                Ok(bytes) => bytes,
-                Err(e) => {
-                    eprintln!("Failed reading .mp3 file: {}", e);
+        if let Some(parsed) = filename_profiles.parse(filename) {
+            let ParsedFilename { profile_name, timestamp, talkgroup: talkgroup_id, radio: radio_id, .. } = parsed;
+            debug!(
+                "Uploading -> profile: {}, timestamp: {}, talkgroup: {}, radio: {:?}",
+                profile_name, timestamp, talkgroup_id, radio_id
+            );
+
+            // 1b) Probe the mp3 for real audio metadata. A probe that couldn't run at all
+            // (ffprobe missing, crashed, or produced unparseable output) is handled
+            // gracefully: we still upload, just without the extra fields. A probe that
+            // ran successfully and reports no audio stream means the recording itself
+            // is corrupt or empty (e.g. a control-channel blip), so that one is dropped
+            // instead, same as one shorter than `config.min_duration_seconds`.
+            let audio_info = match probe::probe(&mp3_path).await {
+                Ok(info) => Some(info),
+                Err(probe::ProbeError::NoAudioStream) => {
+                    info!("Skipping {:?}: no audio stream present", mp3_path);
                     clear_in_progress(&signature);
                     return;
                 }
+                Err(e) => {
+                    warn!("ffprobe failed for {:?}, uploading without audio metadata: {}", mp3_path, e);
+                    None
+                }
             };
 
-            println!(
-                "Uploading -> timestamp: {}, talkgroup: {}, radio: {}",
-                timestamp, talkgroup_id, radio_id
-            );
+            if let Some(info) = &audio_info {
+                if info.duration_seconds < config.min_duration_seconds {
+                    info!(
+                        "Skipping {:?}: duration {:.2}s below minimum {:.2}s",
+                        mp3_path, info.duration_seconds, config.min_duration_seconds
+                    );
+                    clear_in_progress(&signature);
+                    return;
+                }
+            }
+
+            let meta = CallMeta {
+                talkgroup_id,
+                timestamp,
+                radio_id,
+                duration_seconds: audio_info.as_ref().map(|i| i.duration_seconds),
+                codec: audio_info.as_ref().map(|i| i.codec.clone()),
+                sample_rate: audio_info.as_ref().and_then(|i| i.sample_rate),
+                bit_rate: audio_info.as_ref().and_then(|i| i.bit_rate),
+            };
 
-            let mp3_part = Part::bytes(mp3_bytes)
-                .file_name(filename.to_string())
-                .mime_str("audio/mpeg")
-                .expect("Invalid MIME type");
-            let txt_filename = txt_path.file_name().unwrap().to_str().unwrap();
-            let txt_part = Part::bytes(txt_bytes)
-                .file_name(txt_filename.to_string())
-                .mime_str("text/plain")
-                .expect("Invalid MIME type");
-
-            let form = Form::new()
-                .text("talkgroupId", talkgroup_id)
-                .text("timestamp", timestamp)
-                .text("radioId", radio_id)
-                .part("mp3", mp3_part)
-                .part("transcription", txt_part);
-
-            // Perform the upload
-            match CLIENT
-                .post(API_URL.as_str())
-                .header("X-API-Key", API_KEY.as_str())
-                .multipart(form)
-                .send()
-                .await
-            {
-                Ok(res) => {
-                    println!("Upload response: {:?}", res);
-
-                    // If status is success (2xx) or 409 Conflict, we will mark it as processed
-                    // so we never attempt to upload this exact file again.
-                    if res.status().is_success() || res.status() == reqwest::StatusCode::CONFLICT {
-                        println!("Marking file as processed to prevent duplicate uploads.");
-                        mark_as_processed(signature.clone());
-                    } else {
-                        eprintln!("Unexpected server status: {}", res.status());
+            // 2) Hand the pair to whichever backend was configured.
+            let started_at = Instant::now();
+            let result = store.store(&meta, &mp3_path, &txt_path).await;
+            ::metrics::histogram!(metrics::UPLOAD_DURATION_SECONDS)
+                .record(started_at.elapsed().as_secs_f64());
+
+            match result {
+                Ok(StoreOutcome::Stored) => {
+                    ::metrics::counter!(metrics::UPLOADS_SUCCEEDED).increment(1);
+                    if config.log_completed_requests {
+                        info!("Stored '{}', marking as processed.", signature.stem);
+                    }
+                    dedup.mark_as_processed(signature.clone());
+                }
+                Ok(StoreOutcome::Duplicate) => {
+                    ::metrics::counter!(metrics::UPLOADS_SKIPPED_DUPLICATE).increment(1);
+                    if config.log_completed_requests {
+                        info!("'{}' already present at backend, marking as processed.", signature.stem);
                     }
+                    dedup.mark_as_processed(signature.clone());
+                }
+                Err(e) if e.is_retryable() => {
+                    ::metrics::counter!(metrics::UPLOADS_FAILED).increment(1);
+                    warn!("Upload failed ({}), scheduling retry: {}", e, signature.stem);
+                    retry_queue.enqueue(mp3_path.clone(), txt_path.clone(), meta, &signature);
                 }
                 Err(e) => {
-                    eprintln!("Upload failed: {}", e);
+                    ::metrics::counter!(metrics::UPLOADS_FAILED).increment(1);
+                    error!("Upload failed permanently, dropping: {}", e);
                 }
             }
 
@@ -317,32 +390,18 @@ async fn process_and_upload(path: &PathBuf) {
     }
 }
 
-/// Checks if a file with this signature (stem, size, modified) has already been processed.
-fn has_already_been_processed(signature: &ProcessedFile) -> bool {
-    let processed_files = PROCESSED_FILES.lock().unwrap();
-    processed_files.contains(signature)
-}
-
-/// Marks a file as processed by adding it to the ring buffer, which keeps up to 25 entries.
-fn mark_as_processed(signature: ProcessedFile) {
-    let mut processed_files = PROCESSED_FILES.lock().unwrap();
-    processed_files.push_back(signature);
-    while processed_files.len() > 25 {
-        processed_files.pop_front();
-    }
-}
-
 /// Removes the signature from the set of in-progress uploads, ensuring
 /// that we can re-attempt if the original upload fails for unexpected reasons.
 fn clear_in_progress(signature: &ProcessedFile) {
     let mut in_progress = UPLOADS_IN_PROGRESS.lock().unwrap();
     in_progress.remove(signature);
+    ::metrics::gauge!(metrics::UPLOADS_IN_PROGRESS).set(in_progress.len() as f64);
 }
 
 /// Given a file path like `.../20241223_204051North_Carolina_VIPER_Cleveland_T-BennsKControl__TO_P52189_[52193]_FROM_2151975.mp3`,
 /// we pair it with `.../20241223_204051North_Carolina_VIPER_Cleveland_T-BennsKControl__TO_P52189_[52193]_FROM_2151975.txt`
 /// if both exist.
-fn extract_file_info(file_path: &PathBuf) -> Option<(PathBuf, PathBuf)> {
+fn extract_file_info(file_path: &Path) -> Option<(PathBuf, PathBuf)> {
     let file_stem = file_path.file_stem()?.to_str()?;
     let parent_dir = file_path.parent()?;
     let mp3_path = parent_dir.join(format!("{}.mp3", file_stem));
@@ -355,37 +414,3 @@ fn extract_file_info(file_path: &PathBuf) -> Option<(PathBuf, PathBuf)> {
     }
 }
 
-/// Updated regex to capture:
-/// - Group 1: `(\d{8}_\d{6})` = the timestamp
-/// - Group 2: `([A-Za-z]?\d+)` = optional letter + digits (e.g. `P52198`)
-///            and we strip out letters in code.
-/// - Optional bracket `(\[[^\]]*\])?` e.g. `[52193]` we ignore
-/// - Group 3: `_FROM_(\d+)` = the radio ID (optional)
-///
-/// Then we remove any leading letters from the talkgroup ID after capture.
-fn parse_filename(filename: &str) -> Option<(String, String, String)> {
-    // This pattern allows e.g.:
-    //  20241223_204146...__TO_P52198_FROM_2499936.mp3  -> talkgroup "P52198" -> final "52198"
-    //  20241223_204051...__TO_P52189_[52193]_FROM_2151975.mp3
-    // And if `_FROM_` is missing, radio_id defaults to "123456"
-    let re = Regex::new(
-        r"(\d{8}_\d{6}).*__TO_([A-Za-z]?\d+)(?:\[[^\]]*\])?(?:_FROM_(\d+))?"
-    ).unwrap();
-
-    re.captures(filename).and_then(|cap| {
-        // Group 1: timestamp
-        let timestamp = cap.get(1)?.as_str().to_string();
-
-        // Group 2: the talkgroup ID, possibly with a letter prefix.
-        // e.g. "P52189" => remove leading letters => "52189"
-        let raw_tg = cap.get(2)?.as_str();
-        let talkgroup_id = raw_tg.trim_start_matches(|c: char| c.is_ascii_alphabetic()).to_string();
-
-        // Group 3: optional radio ID, else default to "123456"
-        let radio_id = cap
-            .get(3)
-            .map_or("123456".to_string(), |m| m.as_str().to_string());
-
-        Some((timestamp, talkgroup_id, radio_id))
-    })
-}
@@ -0,0 +1,32 @@
+use std::net::SocketAddr;
+
+use metrics_exporter_prometheus::PrometheusBuilder;
+
+pub const FILES_PROCESSED: &str = "transcription_uploader_files_processed_total";
+pub const UPLOADS_SUCCEEDED: &str = "transcription_uploader_uploads_succeeded_total";
+pub const UPLOADS_FAILED: &str = "transcription_uploader_uploads_failed_total";
+pub const UPLOADS_SKIPPED_DUPLICATE: &str = "transcription_uploader_uploads_skipped_duplicate_total";
+pub const UPLOADS_IN_PROGRESS: &str = "transcription_uploader_uploads_in_progress";
+pub const UPLOAD_DURATION_SECONDS: &str = "transcription_uploader_upload_duration_seconds";
+
+/// Installs the Prometheus recorder and starts serving `/metrics` on
+/// `listen_addr` (see `metrics_listen_addr` in [`crate::config`]), so
+/// operators can scrape the watcher's health instead of tailing logs.
+pub fn install(listen_addr: &str) {
+    let addr: SocketAddr = match listen_addr.parse() {
+        Ok(addr) => addr,
+        Err(e) => {
+            tracing::error!("Invalid metrics_listen_addr {:?}: {}", listen_addr, e);
+            return;
+        }
+    };
+
+    if let Err(e) = PrometheusBuilder::new()
+        .with_http_listener(addr)
+        .install()
+    {
+        tracing::error!("Failed to install Prometheus metrics exporter: {}", e);
+        return;
+    }
+    tracing::info!("Serving Prometheus metrics on {}", addr);
+}
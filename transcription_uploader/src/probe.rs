@@ -0,0 +1,183 @@
+use std::path::Path;
+
+use serde::Deserialize;
+use tokio::process::Command;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProbeError {
+    #[error("failed to launch ffprobe: {0}")]
+    Spawn(#[from] std::io::Error),
+    #[error("ffprobe exited with a non-zero status")]
+    NonZeroExit,
+    #[error("failed to parse ffprobe output: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("no audio stream present")]
+    NoAudioStream,
+}
+
+/// The subset of `ffprobe -show_streams -show_format` we actually care about.
+#[derive(Debug, Clone)]
+pub struct AudioInfo {
+    pub duration_seconds: f64,
+    pub codec: String,
+    pub sample_rate: Option<u32>,
+    pub bit_rate: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FfprobeOutput {
+    #[serde(default)]
+    streams: Vec<FfprobeStream>,
+    format: Option<FfprobeFormat>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FfprobeStream {
+    codec_type: String,
+    codec_name: Option<String>,
+    #[serde(default, deserialize_with = "string_as_u32")]
+    sample_rate: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FfprobeFormat {
+    #[serde(default, deserialize_with = "string_as_f64")]
+    duration: Option<f64>,
+    #[serde(default, deserialize_with = "string_as_u64")]
+    bit_rate: Option<u64>,
+}
+
+fn string_as_f64<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s: Option<String> = Option::deserialize(deserializer)?;
+    Ok(s.and_then(|s| s.parse().ok()))
+}
+
+fn string_as_u64<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s: Option<String> = Option::deserialize(deserializer)?;
+    Ok(s.and_then(|s| s.parse().ok()))
+}
+
+fn string_as_u32<'de, D>(deserializer: D) -> Result<Option<u32>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s: Option<String> = Option::deserialize(deserializer)?;
+    Ok(s.and_then(|s| s.parse().ok()))
+}
+
+/// Runs `ffprobe` against `path` and extracts duration/codec/sample-rate/bitrate,
+/// rejecting files with no audio stream (corrupt or truncated recordings) or
+/// an empty stream list. Callers should treat an `Err` here as "couldn't
+/// validate this file" rather than a reason to panic.
+pub async fn probe(path: &Path) -> Result<AudioInfo, ProbeError> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "quiet",
+            "-print_format",
+            "json",
+            "-show_format",
+            "-show_streams",
+        ])
+        .arg(path)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(ProbeError::NonZeroExit);
+    }
+
+    let parsed: FfprobeOutput = serde_json::from_slice(&output.stdout)?;
+
+    let audio_stream = parsed
+        .streams
+        .iter()
+        .find(|s| s.codec_type == "audio")
+        .ok_or(ProbeError::NoAudioStream)?;
+
+    let duration_seconds = parsed
+        .format
+        .as_ref()
+        .and_then(|f| f.duration)
+        .unwrap_or(0.0);
+
+    Ok(AudioInfo {
+        duration_seconds,
+        codec: audio_stream
+            .codec_name
+            .clone()
+            .unwrap_or_else(|| "unknown".to_string()),
+        sample_rate: audio_stream.sample_rate,
+        bit_rate: parsed.format.as_ref().and_then(|f| f.bit_rate),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_realistic_ffprobe_output() {
+        let json = r#"{
+            "streams": [
+                {"codec_type": "audio", "codec_name": "mp3", "sample_rate": "44100"}
+            ],
+            "format": {"duration": "12.345000", "bit_rate": "64000"}
+        }"#;
+        let parsed: FfprobeOutput = serde_json::from_str(json).unwrap();
+
+        let stream = &parsed.streams[0];
+        assert_eq!(stream.codec_type, "audio");
+        assert_eq!(stream.codec_name.as_deref(), Some("mp3"));
+        assert_eq!(stream.sample_rate, Some(44100));
+
+        let format = parsed.format.unwrap();
+        assert_eq!(format.duration, Some(12.345));
+        assert_eq!(format.bit_rate, Some(64_000));
+    }
+
+    #[test]
+    fn missing_format_and_optional_stream_fields_become_none() {
+        let json = r#"{
+            "streams": [{"codec_type": "video"}],
+            "format": null
+        }"#;
+        let parsed: FfprobeOutput = serde_json::from_str(json).unwrap();
+
+        let stream = &parsed.streams[0];
+        assert_eq!(stream.codec_type, "video");
+        assert_eq!(stream.codec_name, None);
+        assert_eq!(stream.sample_rate, None);
+        assert!(parsed.format.is_none());
+    }
+
+    #[test]
+    fn missing_streams_array_defaults_to_empty() {
+        let json = r#"{"format": {"duration": "1.0"}}"#;
+        let parsed: FfprobeOutput = serde_json::from_str(json).unwrap();
+        assert!(parsed.streams.is_empty());
+    }
+
+    #[test]
+    fn string_as_f64_ignores_unparseable_values() {
+        assert_eq!(
+            string_as_f64(serde_json::Value::String("not a number".to_string())).unwrap(),
+            None
+        );
+        assert_eq!(string_as_f64(serde_json::Value::Null).unwrap(), None);
+    }
+
+    #[test]
+    fn string_as_u64_parses_digit_strings() {
+        assert_eq!(
+            string_as_u64(serde_json::Value::String("64000".to_string())).unwrap(),
+            Some(64_000)
+        );
+    }
+}
@@ -0,0 +1,279 @@
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tokio::time::sleep;
+use tracing::{error, info, warn};
+
+use crate::config::Config;
+use crate::dedup::DedupStore;
+use crate::store::{CallMeta, Store, StoreOutcome};
+use crate::ProcessedFile;
+
+/// How often the worker wakes up to check for due entries.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The tunable part of the retry policy: base/max backoff and how many
+/// attempts to make before giving up permanently. Read from [`Config`] so
+/// operators can tune it without recompiling.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub base_backoff: Duration,
+    pub max_backoff: Duration,
+    pub max_attempts: u32,
+}
+
+impl From<&Config> for RetryPolicy {
+    fn from(config: &Config) -> Self {
+        Self {
+            base_backoff: config.retry_base_backoff,
+            max_backoff: config.retry_max_backoff,
+            max_attempts: config.retry_max_attempts,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PendingUpload {
+    mp3_path: PathBuf,
+    txt_path: PathBuf,
+    meta: CallMeta,
+    signature_stem: String,
+    signature_size: u64,
+    signature_modified: u64,
+    attempts: u32,
+    next_attempt_at: u64,
+}
+
+/// A durable queue of uploads that failed with a transient error, retried by
+/// a background worker with exponential backoff. Persisting the queue in
+/// sled means uploads survive a restart instead of being lost the moment
+/// `process_and_upload` gives up on them.
+pub struct RetryQueue {
+    db: sled::Db,
+    policy: RetryPolicy,
+}
+
+impl RetryQueue {
+    pub fn open(path: &std::path::Path, policy: RetryPolicy) -> sled::Result<Self> {
+        Ok(Self {
+            db: sled::open(path)?,
+            policy,
+        })
+    }
+
+    /// Enqueues a failed upload for retry after `policy.base_backoff`.
+    pub fn enqueue(&self, mp3_path: PathBuf, txt_path: PathBuf, meta: CallMeta, signature: &ProcessedFile) {
+        let entry = PendingUpload {
+            mp3_path,
+            txt_path,
+            meta,
+            signature_stem: signature.stem.clone(),
+            signature_size: signature.size,
+            signature_modified: signature
+                .modified
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or(Duration::ZERO)
+                .as_secs(),
+            attempts: 0,
+            next_attempt_at: now_secs() + self.policy.base_backoff.as_secs(),
+        };
+        self.insert(&entry);
+    }
+
+    fn insert(&self, entry: &PendingUpload) {
+        let key = format!(
+            "{}\0{}\0{}",
+            entry.signature_stem, entry.signature_size, entry.signature_modified
+        );
+        match serde_json::to_vec(entry) {
+            Ok(bytes) => {
+                if let Err(e) = self.db.insert(key.as_bytes(), bytes) {
+                    error!("Failed to persist retry entry for {}: {}", entry.signature_stem, e);
+                } else {
+                    let _ = self.db.flush();
+                }
+            }
+            Err(e) => error!("Failed to serialize retry entry for {}: {}", entry.signature_stem, e),
+        }
+    }
+
+    fn remove(&self, entry: &PendingUpload) {
+        let key = format!(
+            "{}\0{}\0{}",
+            entry.signature_stem, entry.signature_size, entry.signature_modified
+        );
+        let _ = self.db.remove(key.as_bytes());
+        let _ = self.db.flush();
+    }
+
+    fn due_entries(&self) -> Vec<PendingUpload> {
+        let now = now_secs();
+        self.db
+            .iter()
+            .values()
+            .filter_map(|v| v.ok())
+            .filter_map(|bytes| serde_json::from_slice::<PendingUpload>(&bytes).ok())
+            .filter(|entry| entry.next_attempt_at <= now)
+            .collect()
+    }
+
+    /// Spawns the background task that retries due entries with exponential
+    /// backoff until they succeed, hit `policy.max_attempts`, or fail permanently.
+    pub fn spawn_worker(
+        self: Arc<Self>,
+        store: Arc<dyn Store>,
+        dedup: Arc<DedupStore>,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                for mut entry in self.due_entries() {
+                    match store.store(&entry.meta, &entry.mp3_path, &entry.txt_path).await {
+                        Ok(StoreOutcome::Stored) | Ok(StoreOutcome::Duplicate) => {
+                            ::metrics::counter!(crate::metrics::UPLOADS_SUCCEEDED).increment(1);
+                            info!(
+                                "Retry succeeded for '{}' after {} attempt(s).",
+                                entry.signature_stem,
+                                entry.attempts + 1
+                            );
+                            dedup.mark_as_processed(ProcessedFile {
+                                stem: entry.signature_stem.clone(),
+                                size: entry.signature_size,
+                                modified: UNIX_EPOCH + Duration::from_secs(entry.signature_modified),
+                            });
+                            self.remove(&entry);
+                        }
+                        Err(e) if e.is_retryable() && entry.attempts + 1 < self.policy.max_attempts => {
+                            entry.attempts += 1;
+                            let backoff = backoff_for(&self.policy, entry.attempts);
+                            entry.next_attempt_at = now_secs() + backoff.as_secs();
+                            warn!(
+                                "Retry {} for '{}' failed ({}), next attempt in {:?}.",
+                                entry.attempts, entry.signature_stem, e, backoff
+                            );
+                            self.insert(&entry);
+                        }
+                        Err(e) => {
+                            ::metrics::counter!(crate::metrics::UPLOADS_FAILED).increment(1);
+                            error!(
+                                "Giving up on '{}' after {} attempt(s): {}",
+                                entry.signature_stem, entry.attempts + 1, e
+                            );
+                            self.remove(&entry);
+                        }
+                    }
+                }
+
+                sleep(POLL_INTERVAL).await;
+            }
+        })
+    }
+}
+
+fn backoff_for(policy: &RetryPolicy, attempts: u32) -> Duration {
+    let exp = policy.base_backoff.saturating_mul(1 << attempts.min(16));
+    let capped = exp.min(policy.max_backoff);
+    let jitter_ms = rand::thread_rng().gen_range(0..250);
+    capped + Duration::from_millis(jitter_ms)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            base_backoff: Duration::from_secs(2),
+            max_backoff: Duration::from_secs(300),
+            max_attempts: 10,
+        }
+    }
+
+    fn meta() -> CallMeta {
+        CallMeta {
+            talkgroup_id: "52189".to_string(),
+            timestamp: "20241223_204051".to_string(),
+            radio_id: Some("2151975".to_string()),
+            duration_seconds: Some(4.2),
+            codec: Some("mp3".to_string()),
+            sample_rate: Some(8_000),
+            bit_rate: Some(64_000),
+        }
+    }
+
+    fn signature(stem: &str) -> ProcessedFile {
+        ProcessedFile {
+            stem: stem.to_string(),
+            size: 1,
+            modified: UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn backoff_for_doubles_and_caps_at_max_backoff() {
+        let policy = policy();
+
+        // Jitter adds up to 250ms on top of the doubled/capped base, so
+        // compare against the [exp, exp + 250ms] range rather than equality.
+        let first = backoff_for(&policy, 1);
+        assert!(first >= Duration::from_secs(4) && first < Duration::from_millis(4_250));
+
+        let second = backoff_for(&policy, 2);
+        assert!(second >= Duration::from_secs(8) && second < Duration::from_millis(8_250));
+
+        // 2s * 2^10 would be ~34 minutes, far past the 300s max_backoff.
+        let capped = backoff_for(&policy, 10);
+        assert!(capped >= policy.max_backoff && capped < policy.max_backoff + Duration::from_millis(250));
+    }
+
+    #[test]
+    fn enqueued_entry_is_not_due_before_base_backoff() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = RetryQueue::open(&dir.path().join("retry.sled"), policy()).unwrap();
+
+        queue.enqueue(
+            PathBuf::from("/tmp/a.mp3"),
+            PathBuf::from("/tmp/a.txt"),
+            meta(),
+            &signature("a"),
+        );
+
+        // `enqueue` schedules `next_attempt_at` in the future (base_backoff
+        // from now), so it shouldn't show up as due yet.
+        assert!(queue.due_entries().is_empty());
+    }
+
+    #[test]
+    fn insert_then_remove_clears_the_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = RetryQueue::open(&dir.path().join("retry.sled"), policy()).unwrap();
+
+        let entry = PendingUpload {
+            mp3_path: PathBuf::from("/tmp/a.mp3"),
+            txt_path: PathBuf::from("/tmp/a.txt"),
+            meta: meta(),
+            signature_stem: "a".to_string(),
+            signature_size: 1,
+            signature_modified: 0,
+            attempts: 0,
+            next_attempt_at: 0,
+        };
+
+        queue.insert(&entry);
+        assert_eq!(queue.due_entries().len(), 1);
+
+        queue.remove(&entry);
+        assert!(queue.due_entries().is_empty());
+    }
+}
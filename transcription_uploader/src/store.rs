@@ -0,0 +1,500 @@
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use reqwest::{
+    multipart::{Form, Part},
+    Client, StatusCode,
+};
+
+/// Metadata about a single call recording that a `Store` needs in order to
+/// file it away (HTTP form fields, a directory name, an S3 key, ...).
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CallMeta {
+    pub talkgroup_id: String,
+    pub timestamp: String,
+    /// `None` when the filename-parsing profile that matched doesn't capture
+    /// a radio ID (e.g. no `_FROM_` in the SDRTrunk convention), rather than
+    /// inventing one.
+    pub radio_id: Option<String>,
+    /// Populated from `ffprobe` when probing succeeds; `None` if probing
+    /// failed (we still upload, just without the extra fields).
+    pub duration_seconds: Option<f64>,
+    pub codec: Option<String>,
+    pub sample_rate: Option<u32>,
+    pub bit_rate: Option<u64>,
+}
+
+/// What happened when a recording was handed to a `Store`.
+///
+/// This mirrors the three outcomes `process_and_upload` already distinguishes
+/// for the HTTP case (success, duplicate, failure) so the dedup/in-progress
+/// logic in `main.rs` stays backend-agnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreOutcome {
+    /// The mp3/txt pair was accepted by the backend.
+    Stored,
+    /// The backend already had this exact recording (e.g. HTTP 409).
+    Duplicate,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StoreError {
+    #[error("request error: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("object store error: {0}")]
+    ObjectStore(#[from] object_store::Error),
+    #[error("server rejected upload with status {0}")]
+    UnexpectedStatus(StatusCode),
+}
+
+impl StoreError {
+    /// Whether this failure is worth retrying: connection errors, timeouts,
+    /// and 5xx/429 responses are transient; anything else (4xx other than
+    /// 429, or a local filesystem error that won't resolve on its own) is
+    /// treated as permanent.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            StoreError::Request(e) => e.is_connect() || e.is_timeout() || e.status().is_some_and(|s| {
+                s.is_server_error() || s == StatusCode::TOO_MANY_REQUESTS
+            }),
+            StoreError::Io(e) => io_error_is_retryable(e),
+            StoreError::ObjectStore(e) => object_store_error_is_retryable(e),
+            StoreError::UnexpectedStatus(status) => {
+                status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+            }
+        }
+    }
+}
+
+/// Whether a local filesystem failure is worth retrying. Not-found,
+/// permission-denied, already-exists and invalid-input errors mean the
+/// source/destination path is wrong and retrying with the same arguments
+/// will fail the same way every time; everything
+/// else (disk-full, interrupted syscalls, timeouts on network mounts, ...)
+/// is the kind of transient condition a later attempt can succeed past.
+fn io_error_is_retryable(error: &std::io::Error) -> bool {
+    !matches!(
+        error.kind(),
+        std::io::ErrorKind::NotFound
+            | std::io::ErrorKind::PermissionDenied
+            | std::io::ErrorKind::AlreadyExists
+            | std::io::ErrorKind::InvalidInput
+    )
+}
+
+/// Whether an `object_store` failure is worth retrying. A few variants name
+/// the failure precisely enough to know it'll never succeed on its own
+/// (the object/path simply isn't there, the operation isn't supported, a
+/// config key is unknown, ...); everything else (most client errors, since
+/// the AWS client funnels all non-404/412/409 statuses into `Generic`) is
+/// told apart by the status code embedded in its message, so an auth
+/// failure or a bad request doesn't get retried the same as a 429 or 5xx.
+fn object_store_error_is_retryable(error: &object_store::Error) -> bool {
+    match error {
+        object_store::Error::NotFound { .. }
+        | object_store::Error::AlreadyExists { .. }
+        | object_store::Error::InvalidPath { .. }
+        | object_store::Error::NotSupported { .. }
+        | object_store::Error::NotImplemented
+        | object_store::Error::UnknownConfigurationKey { .. } => false,
+        object_store::Error::Generic { source, .. } => !is_permanent_client_status(source.as_ref()),
+        object_store::Error::Precondition { .. }
+        | object_store::Error::NotModified { .. }
+        | object_store::Error::JoinError { .. } => true,
+    }
+}
+
+/// `object_store`'s retrying HTTP client renders client errors as
+/// `"...status <code> <reason>: ..."`; a 4xx other than 429 (rate limited,
+/// worth retrying) means the request itself is bad (bad credentials,
+/// access denied, malformed request, ...) and won't succeed by retrying.
+fn is_permanent_client_status(source: &(dyn std::error::Error + 'static)) -> bool {
+    let message = source.to_string();
+    message
+        .find("status ")
+        .and_then(|i| message[i + "status ".len()..].split_whitespace().next())
+        .and_then(|code| code.parse::<u16>().ok())
+        .is_some_and(|code| (400..500).contains(&code) && code != 429)
+}
+
+pub type StoreResult<T> = Result<T, StoreError>;
+
+/// Something that a finished mp3/txt transcription pair can be archived to.
+///
+/// Implementations are selected at startup based on config (see
+/// [`crate::config`]) so that `process_and_upload` never has to know whether
+/// it is talking to the web API, a local archive directory, or an S3 bucket.
+#[async_trait]
+pub trait Store: Send + Sync {
+    async fn store(&self, meta: &CallMeta, mp3: &Path, txt: &Path) -> StoreResult<StoreOutcome>;
+}
+
+/// The original behavior: POST a multipart form to the web API.
+pub struct HttpStore {
+    client: Client,
+    api_url: String,
+    api_key: String,
+}
+
+impl HttpStore {
+    pub fn new(client: Client, api_url: String, api_key: String) -> Self {
+        Self {
+            client,
+            api_url,
+            api_key,
+        }
+    }
+}
+
+#[async_trait]
+impl Store for HttpStore {
+    async fn store(&self, meta: &CallMeta, mp3: &Path, txt: &Path) -> StoreResult<StoreOutcome> {
+        let mp3_bytes = tokio::fs::read(mp3).await?;
+        let txt_bytes = tokio::fs::read(txt).await?;
+
+        let mp3_filename = mp3
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or("recording.mp3")
+            .to_string();
+        let txt_filename = txt
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or("transcription.txt")
+            .to_string();
+
+        let mp3_part = Part::bytes(mp3_bytes)
+            .file_name(mp3_filename)
+            .mime_str("audio/mpeg")
+            .expect("Invalid MIME type");
+        let txt_part = Part::bytes(txt_bytes)
+            .file_name(txt_filename)
+            .mime_str("text/plain")
+            .expect("Invalid MIME type");
+
+        let mut form = Form::new()
+            .text("talkgroupId", meta.talkgroup_id.clone())
+            .text("timestamp", meta.timestamp.clone());
+        if let Some(radio_id) = &meta.radio_id {
+            form = form.text("radioId", radio_id.clone());
+        }
+        if let Some(duration_seconds) = meta.duration_seconds {
+            form = form.text("durationSeconds", duration_seconds.to_string());
+        }
+        if let Some(codec) = &meta.codec {
+            form = form.text("codec", codec.clone());
+        }
+        if let Some(sample_rate) = meta.sample_rate {
+            form = form.text("sampleRate", sample_rate.to_string());
+        }
+        if let Some(bit_rate) = meta.bit_rate {
+            form = form.text("bitRate", bit_rate.to_string());
+        }
+        let form = form.part("mp3", mp3_part).part("transcription", txt_part);
+
+        let res = self
+            .client
+            .post(&self.api_url)
+            .header("X-API-Key", self.api_key.as_str())
+            .multipart(form)
+            .send()
+            .await?;
+
+        if res.status().is_success() {
+            Ok(StoreOutcome::Stored)
+        } else if res.status() == StatusCode::CONFLICT {
+            Ok(StoreOutcome::Duplicate)
+        } else {
+            Err(StoreError::UnexpectedStatus(res.status()))
+        }
+    }
+}
+
+/// Copies the mp3/txt pair, under their original file names, into
+/// `YYYY/MM/DD/<talkgroup>/` under `archive_root`, so a non-API user can
+/// still keep every transcription.
+pub struct FilesystemStore {
+    archive_root: PathBuf,
+}
+
+impl FilesystemStore {
+    pub fn new(archive_root: PathBuf) -> Self {
+        Self { archive_root }
+    }
+
+    fn date_partition(&self, timestamp: &str) -> PathBuf {
+        // `timestamp` is `YYYYMMDD_HHMMSS`; partition by YYYY/MM/DD.
+        let date_part = timestamp.split('_').next().unwrap_or(timestamp);
+        if date_part.len() == 8 {
+            PathBuf::from(&date_part[0..4])
+                .join(&date_part[4..6])
+                .join(&date_part[6..8])
+        } else {
+            PathBuf::from("unknown-date")
+        }
+    }
+}
+
+#[async_trait]
+impl Store for FilesystemStore {
+    async fn store(&self, meta: &CallMeta, mp3: &Path, txt: &Path) -> StoreResult<StoreOutcome> {
+        let dest_dir = self
+            .archive_root
+            .join(self.date_partition(&meta.timestamp))
+            .join(&meta.talkgroup_id);
+        tokio::fs::create_dir_all(&dest_dir).await?;
+
+        let mp3_name = mp3.file_name().ok_or_else(|| {
+            StoreError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "mp3 path has no file name",
+            ))
+        })?;
+        let txt_name = txt.file_name().ok_or_else(|| {
+            StoreError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "txt path has no file name",
+            ))
+        })?;
+
+        let mp3_dest = dest_dir.join(mp3_name);
+        let txt_dest = dest_dir.join(txt_name);
+
+        if mp3_dest.exists() && txt_dest.exists() {
+            return Ok(StoreOutcome::Duplicate);
+        }
+
+        tokio::fs::copy(mp3, &mp3_dest).await?;
+        tokio::fs::copy(txt, &txt_dest).await?;
+
+        Ok(StoreOutcome::Stored)
+    }
+}
+
+/// Uploads the mp3/txt pair as two objects in an S3-compatible bucket,
+/// keyed `<prefix>/<talkgroup>/<file name>`.
+pub struct S3Store {
+    store: object_store::aws::AmazonS3,
+    prefix: String,
+}
+
+impl S3Store {
+    pub fn new(store: object_store::aws::AmazonS3, prefix: String) -> Self {
+        Self { store, prefix }
+    }
+
+    fn key_for(&self, meta: &CallMeta, file_name: &str) -> object_store::path::Path {
+        let key = format!(
+            "{}/{}/{}",
+            self.prefix.trim_matches('/'),
+            meta.talkgroup_id,
+            file_name
+        );
+        object_store::path::Path::from(key)
+    }
+}
+
+#[async_trait]
+impl Store for S3Store {
+    async fn store(&self, meta: &CallMeta, mp3: &Path, txt: &Path) -> StoreResult<StoreOutcome> {
+        use object_store::ObjectStore;
+
+        let mp3_name = mp3
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or("recording.mp3");
+        let txt_name = txt
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or("transcription.txt");
+
+        let mp3_key = self.key_for(meta, mp3_name);
+        let txt_key = self.key_for(meta, txt_name);
+        if self.store.head(&mp3_key).await.is_ok() && self.store.head(&txt_key).await.is_ok() {
+            return Ok(StoreOutcome::Duplicate);
+        }
+
+        let mp3_bytes = tokio::fs::read(mp3).await?;
+        let txt_bytes = tokio::fs::read(txt).await?;
+
+        self.store.put(&mp3_key, mp3_bytes.into()).await?;
+        self.store.put(&txt_key, txt_bytes.into()).await?;
+
+        Ok(StoreOutcome::Stored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn io_not_found_is_permanent() {
+        let err = std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+        assert!(!io_error_is_retryable(&err));
+        assert!(!StoreError::Io(err).is_retryable());
+    }
+
+    #[test]
+    fn io_permission_denied_is_permanent() {
+        let err = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied");
+        assert!(!io_error_is_retryable(&err));
+    }
+
+    #[test]
+    fn io_interrupted_is_retryable() {
+        let err = std::io::Error::new(std::io::ErrorKind::Interrupted, "interrupted");
+        assert!(io_error_is_retryable(&err));
+        assert!(StoreError::Io(err).is_retryable());
+    }
+
+    fn generic_with_message(message: &str) -> object_store::Error {
+        object_store::Error::Generic {
+            store: "S3",
+            source: Box::new(std::io::Error::other(message.to_string())),
+        }
+    }
+
+    #[test]
+    fn not_found_is_permanent() {
+        let err = object_store::Error::NotFound {
+            path: "bucket/key".to_string(),
+            source: Box::new(std::io::Error::other("missing")),
+        };
+        assert!(!object_store_error_is_retryable(&err));
+    }
+
+    #[test]
+    fn generic_403_is_permanent() {
+        let err = generic_with_message("Client error with status 403 Forbidden: access denied");
+        assert!(!object_store_error_is_retryable(&err));
+    }
+
+    #[test]
+    fn generic_401_is_permanent() {
+        let err = generic_with_message("Client error with status 401 Unauthorized: bad credentials");
+        assert!(!object_store_error_is_retryable(&err));
+    }
+
+    #[test]
+    fn generic_429_is_retryable() {
+        let err = generic_with_message("Client error with status 429 Too Many Requests: slow down");
+        assert!(object_store_error_is_retryable(&err));
+    }
+
+    #[test]
+    fn generic_500_is_retryable() {
+        let err = generic_with_message("Error after 3 retries in 1s, source: status 500 Internal Server Error");
+        assert!(object_store_error_is_retryable(&err));
+    }
+
+    #[test]
+    fn generic_with_no_status_in_message_defaults_to_retryable() {
+        let err = generic_with_message("connection reset by peer");
+        assert!(object_store_error_is_retryable(&err));
+    }
+
+    #[test]
+    fn store_error_is_retryable_delegates_to_object_store_classification() {
+        let err = StoreError::ObjectStore(object_store::Error::NotImplemented);
+        assert!(!err.is_retryable());
+
+        let err = StoreError::ObjectStore(object_store::Error::JoinError {
+            source: unjoinable_task(),
+        });
+        assert!(err.is_retryable());
+    }
+
+    fn unjoinable_task() -> tokio::task::JoinError {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let handle = tokio::spawn(async { panic!("expected for test") });
+            handle.await.unwrap_err()
+        })
+    }
+
+    fn meta() -> CallMeta {
+        CallMeta {
+            talkgroup_id: "52189".to_string(),
+            timestamp: "20241223_204051".to_string(),
+            radio_id: Some("2151975".to_string()),
+            duration_seconds: Some(12.3),
+            codec: Some("mp3".to_string()),
+            sample_rate: Some(44_100),
+            bit_rate: Some(64_000),
+        }
+    }
+
+    #[test]
+    fn date_partition_splits_yyyymmdd_hhmmss_into_year_month_day() {
+        let store = FilesystemStore::new(PathBuf::from("/archive"));
+        assert_eq!(
+            store.date_partition("20241223_204051"),
+            PathBuf::from("2024").join("12").join("23")
+        );
+    }
+
+    #[test]
+    fn date_partition_falls_back_for_an_unparseable_timestamp() {
+        let store = FilesystemStore::new(PathBuf::from("/archive"));
+        assert_eq!(store.date_partition("not-a-timestamp"), PathBuf::from("unknown-date"));
+    }
+
+    #[tokio::test]
+    async fn filesystem_store_copies_a_new_pair_and_reports_stored() {
+        let archive = tempfile::tempdir().unwrap();
+        let source = tempfile::tempdir().unwrap();
+        let mp3 = source.path().join("call.mp3");
+        let txt = source.path().join("call.txt");
+        tokio::fs::write(&mp3, b"audio").await.unwrap();
+        tokio::fs::write(&txt, b"transcript").await.unwrap();
+
+        let store = FilesystemStore::new(archive.path().to_path_buf());
+        let outcome = store.store(&meta(), &mp3, &txt).await.unwrap();
+
+        assert_eq!(outcome, StoreOutcome::Stored);
+        let dest_dir = archive.path().join("2024/12/23").join("52189");
+        assert!(dest_dir.join("call.mp3").exists());
+        assert!(dest_dir.join("call.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn filesystem_store_reports_duplicate_once_both_files_exist() {
+        let archive = tempfile::tempdir().unwrap();
+        let source = tempfile::tempdir().unwrap();
+        let mp3 = source.path().join("call.mp3");
+        let txt = source.path().join("call.txt");
+        tokio::fs::write(&mp3, b"audio").await.unwrap();
+        tokio::fs::write(&txt, b"transcript").await.unwrap();
+
+        let store = FilesystemStore::new(archive.path().to_path_buf());
+        store.store(&meta(), &mp3, &txt).await.unwrap();
+        let outcome = store.store(&meta(), &mp3, &txt).await.unwrap();
+
+        assert_eq!(outcome, StoreOutcome::Duplicate);
+    }
+
+    #[tokio::test]
+    async fn filesystem_store_does_not_treat_a_partial_previous_copy_as_duplicate() {
+        let archive = tempfile::tempdir().unwrap();
+        let source = tempfile::tempdir().unwrap();
+        let mp3 = source.path().join("call.mp3");
+        let txt = source.path().join("call.txt");
+        tokio::fs::write(&mp3, b"audio").await.unwrap();
+        tokio::fs::write(&txt, b"transcript").await.unwrap();
+
+        let store = FilesystemStore::new(archive.path().to_path_buf());
+        let dest_dir = archive.path().join("2024/12/23").join("52189");
+        tokio::fs::create_dir_all(&dest_dir).await.unwrap();
+        tokio::fs::copy(&mp3, dest_dir.join("call.mp3")).await.unwrap();
+
+        let outcome = store.store(&meta(), &mp3, &txt).await.unwrap();
+
+        assert_eq!(outcome, StoreOutcome::Stored);
+        assert!(dest_dir.join("call.txt").exists());
+    }
+}